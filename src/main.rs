@@ -1,13 +1,14 @@
 use std::env;
 use std::fs::{File, OpenOptions};
-use std::io::{prelude::*, BufReader, BufWriter, SeekFrom};
+use std::io::{prelude::*, BufWriter, SeekFrom};
 use std::path::PathBuf;
 use std::process::Command;
 use std::thread;
 
 use clap::{Parser, ValueEnum};
 use rand::distributions::uniform::UniformFloat;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rand_distr::uniform::UniformSampler;
 use rand_distr::{Distribution, Exp, Normal};
 use tqdm::{pbar, tqdm};
@@ -22,8 +23,33 @@ enum Dist {
     Normal,
     /// Generate values with a exponential distibution
     Exp,
+    /// Generate edge counts from a truncated discrete power-law (scale-free) distribution.
+    /// Only supported for `--edge-dist`.
+    PowerLaw,
 }
 
+#[derive(Clone, Debug, ValueEnum, PartialEq)]
+enum Format {
+    /// Pipe-delimited `SrcID|DstID` CSV edge list
+    Csv,
+    /// Little-endian binary edge list: a `u64` node count header followed by
+    /// fixed 12-byte `(u32 src, u32 dst, f32 weight)` records
+    Binary,
+    /// Same 12-byte edge records as `binary`, but prefixed with a per-node offset table (in place
+    /// of the node-count header) so a reader can seek directly to any node's adjacency list
+    /// instead of scanning the whole file
+    Indexed,
+    /// GFA (Graph Fragment Assembly) text export: an `H` header line, one `S` segment line per
+    /// node, and one `L` link line per edge
+    Gfa,
+}
+
+/// Size in bytes of a single `Indexed` header entry: a `u64` byte offset (from the start of the
+/// combined edge file) of that node's first edge record, plus a trailing `u32` edge count.
+const HEADER_ENTRY_SIZE: u64 = 12;
+/// Size in bytes of a single `Binary`/`Indexed` edge record: `(u32 src, u32 dst, f32 weight)`.
+const EDGE_RECORD_SIZE: u64 = 12;
+
 /// Generate graphs with different edge and property distributions
 #[derive(Clone, Parser, Debug)]
 #[command(version, about)]
@@ -39,6 +65,18 @@ struct Args {
     #[arg(long)]
     edge_dist: Dist,
 
+    /// Minimum degree for the power-law edge distribution (--edge-dist power-law)
+    #[arg(long, default_value = "1")]
+    k_min: usize,
+
+    /// Maximum degree for the power-law edge distribution (--edge-dist power-law)
+    #[arg(long, default_value = "10000")]
+    k_max: usize,
+
+    /// Exponent for the power-law edge distribution (--edge-dist power-law)
+    #[arg(long, default_value = "2.5")]
+    gamma: f64,
+
     /// Node property size distribution
     #[arg(long)]
     node_prop_dist: Dist,
@@ -47,6 +85,10 @@ struct Args {
     #[arg(long, default_value = "none")]
     edge_prop_dist: Dist,
 
+    /// Output format for the edge list
+    #[arg(long, default_value = "csv")]
+    format: Format,
+
     /// Output directory
     #[arg(long, default_value = ".")]
     outdir: PathBuf,
@@ -58,6 +100,27 @@ struct Args {
     /// Generate chunks that can be used to incrementally build the graph - 1 chunk per thread
     #[arg(long)]
     generatechunks: bool,
+
+    /// Seed for deterministic generation. The same seed, nprocs, n_nodes, and distributions
+    /// always produce byte-identical output; omitting it generates a different graph every run.
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+/// Derive a chunk's independent RNG seed from the run seed and its chunk id, so that each chunk's
+/// output is reproducible regardless of thread scheduling.
+///
+/// This deliberately avoids `std::collections::hash_map::DefaultHasher`: its algorithm is
+/// explicitly documented as unstable across Rust releases, which would silently break
+/// reproducibility of generated fixtures across compiler upgrades. The mix step below (SplitMix64)
+/// has no such guarantee to break.
+fn chunk_seed(seed: u64, id: usize) -> u64 {
+    let mut z = seed
+        .wrapping_add(id as u64)
+        .wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
 }
 
 fn create_dir(dir: &PathBuf) {
@@ -68,17 +131,11 @@ fn create_dir(dir: &PathBuf) {
         .expect("failed to create outdir");
 }
 
-fn get_prop<R>(
-    rng: &mut R,
-    data_source: &mut File,
-    prop_dist: &Dist,
-    min_prop_size: usize,
-    prop_range: usize,
-) -> String
+fn sample_prop_dist<R>(rng: &mut R, prop_dist: &Dist) -> f64
 where
     R: Rng,
 {
-    let f: f64 = match prop_dist {
+    match prop_dist {
         Dist::None => 0.,
         Dist::Uniform => UniformFloat::<f64>::new(0.0, 1.0).sample(rng),
         Dist::Normal => {
@@ -92,18 +149,36 @@ where
             }
         }
         Dist::Exp => Exp::new(0.5).unwrap().sample(rng),
-    };
+        Dist::PowerLaw => panic!("power-law distribution is only supported for --edge-dist"),
+    }
+}
+
+fn get_prop<R>(rng: &mut R, prop_dist: &Dist, min_prop_size: usize, prop_range: usize) -> String
+where
+    R: Rng,
+{
+    let f = sample_prop_dist(rng, prop_dist);
     let mut buf = vec![0u8; (min_prop_size + ((f * prop_range as f64) as usize)) / 3];
-    data_source
-        .read_exact(&mut buf)
-        .expect("Failed to read from urandom");
+    // Draw the payload from the same (seeded, per-chunk) rng as everything else, so that a given
+    // --seed reproduces property content byte-for-byte, not just node ids and edge counts.
+    rng.fill(&mut buf[..]);
     urlencoding::encode_binary(&buf).to_string()
 }
 
-fn get_n_edges<R>(rng: &mut R, edge_dist: &Dist) -> usize
+fn get_n_edges<R>(rng: &mut R, edge_dist: &Dist, k_min: usize, k_max: usize, gamma: f64) -> usize
 where
     R: Rng,
 {
+    if *edge_dist == Dist::PowerLaw {
+        assert!(gamma > 1., "power-law exponent (gamma) must be > 1");
+        // Inverse-transform sampling of a truncated discrete power law: draw u uniform in [0, 1)
+        // and map it through the power law's inverse CDF, k = k_min * (1 - u)^(-1 / (gamma - 1)).
+        let u: f64 = UniformFloat::<f64>::new(0.0, 1.0).sample(rng);
+        let u = u.min(1. - f64::EPSILON); // keep (1 - u) off of 0 so the power doesn't overflow
+        let k = k_min as f64 * (1. - u).powf(-1. / (gamma - 1.));
+        return (k.floor() as usize).min(k_max);
+    }
+
     let f: f64 = match edge_dist {
         Dist::None => 0.,
         Dist::Uniform => UniformFloat::<f64>::new(0.0, 1.0).sample(rng),
@@ -121,30 +196,66 @@ where
             let e = Exp::new(0.5).unwrap().sample(rng);
             e * e
         }
+        Dist::PowerLaw => unreachable!(),
     };
 
     (f * 10000.) as usize
 }
 
+/// Name of the per-chunk edge file for a given output format.
+fn chunk_edges_name(id: usize, format: &Format) -> String {
+    match format {
+        Format::Csv => format!("edges_{}.csv", id),
+        Format::Binary | Format::Indexed => format!("edges_{}.bin", id),
+        Format::Gfa => format!("edges_{}.gfa", id),
+    }
+}
+
+/// Name of the combined edge file for a given output format.
+fn combined_edges_name(format: &Format) -> &'static str {
+    match format {
+        Format::Csv => "edges.csv",
+        Format::Binary => "edges.bin",
+        Format::Indexed => "edges.idx",
+        Format::Gfa => "graph.gfa",
+    }
+}
+
+/// Name of the per-chunk node-offset header file, used only by the `Indexed` format.
+fn chunk_header_name(id: usize) -> String {
+    format!("header_{}.bin", id)
+}
+
 fn generate_chunk(args: Args, id: usize) {
     let mut nodefile = BufWriter::new(
         File::create(format!("nodes_{}.csv", id)).expect("Failed to create thread-local nodes.csv"),
     );
     let mut edgefile = BufWriter::new(
-        File::create(format!("edges_{}.csv", id)).expect("Failed to create thread-local edges.csv"),
+        File::create(chunk_edges_name(id, &args.format))
+            .expect("Failed to create thread-local edge file"),
     );
     let mut statsfile = BufWriter::new(
         File::create(format!("stats_{}.txt", id)).expect("Failed to create thread-local stats.txt"),
     );
+    let mut headerfile = if args.format == Format::Indexed {
+        Some(BufWriter::new(
+            File::create(chunk_header_name(id)).expect("Failed to create thread-local header file"),
+        ))
+    } else {
+        None
+    };
+    // Byte offset of the next adjacency list within this chunk's own edge file; only tracked for
+    // the `Indexed` format.
+    let mut edge_file_offset: u64 = 0;
 
     let prop_range = args.max_prop_size - args.min_prop_size;
-    let mut data_source = File::open("/dev/urandom").expect("Failed to open urandom");
 
     let chunksize = args.n_nodes / args.nprocs;
     let start = chunksize * id;
     let end = std::cmp::min(start + chunksize, args.n_nodes);
 
-    let mut rng = rand::thread_rng();
+    let base_seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(chunk_seed(base_seed, id));
 
     let has_node_props = args.node_prop_dist != Dist::None;
     let has_edge_props = args.edge_prop_dist != Dist::None;
@@ -175,16 +286,21 @@ fn generate_chunk(args: Args, id: usize) {
     for nid in tqdm(start..end) {
         let nid_str = &nid.to_string();
 
-        node_line.push_str(&nid_str);
-        if has_node_props {
-            node_line.push_str("|");
-            node_line.push_str(&get_prop(
+        let node_prop = if has_node_props {
+            Some(get_prop(
                 &mut rng,
-                &mut data_source,
                 &args.node_prop_dist,
                 args.min_prop_size,
                 prop_range,
-            ));
+            ))
+        } else {
+            None
+        };
+
+        node_line.push_str(&nid_str);
+        if let Some(prop) = &node_prop {
+            node_line.push_str("|");
+            node_line.push_str(prop);
         }
         node_line.push_str("\n");
         nodefile
@@ -192,7 +308,22 @@ fn generate_chunk(args: Args, id: usize) {
             .expect("Failed to write node");
         node_line.clear();
 
-        let n_edges = get_n_edges(&mut rng, &args.edge_dist);
+        if args.format == Format::Gfa {
+            // GFA1's <sequence> column only allows `\*|[A-Za-z=.]+`, which the urlencoded property
+            // blob doesn't honor - leave it as `*` (sequence not specified) and fold the property
+            // into an optional tag instead, same as edge properties do with `ep:Z:`.
+            let mut line = format!("S\t{}\t*", nid);
+            if let Some(prop) = &node_prop {
+                line.push_str("\tdp:Z:");
+                line.push_str(prop);
+            }
+            line.push_str("\n");
+            edgefile
+                .write_all(line.as_bytes())
+                .expect("Failed to write GFA segment");
+        }
+
+        let n_edges = get_n_edges(&mut rng, &args.edge_dist, args.k_min, args.k_max, args.gamma);
         stats_line.push_str(&nid_str);
         stats_line.push_str(" ");
         stats_line.push_str(&n_edges.to_string());
@@ -202,6 +333,16 @@ fn generate_chunk(args: Args, id: usize) {
             .expect("Failed to write stats");
         stats_line.clear();
 
+        if let Some(headerfile) = headerfile.as_mut() {
+            headerfile
+                .write_all(&edge_file_offset.to_le_bytes())
+                .expect("Failed to write header entry");
+            headerfile
+                .write_all(&(n_edges as u32).to_le_bytes())
+                .expect("Failed to write header entry");
+            edge_file_offset += n_edges as u64 * EDGE_RECORD_SIZE;
+        }
+
         edge_line.push_str(&nid_str);
         edge_line.push_str("|");
         let prefix_len = edge_line.len();
@@ -213,29 +354,68 @@ fn generate_chunk(args: Args, id: usize) {
             } else {
                 args.n_nodes
             };
-            let dst = rand::thread_rng().gen_range(0..maxnid);
-            edge_line.push_str(&dst.to_string());
-            if has_edge_props {
-                edge_line.push_str("|");
-                edge_line.push_str(&get_prop(
-                    &mut rng,
-                    &mut data_source,
-                    &args.edge_prop_dist,
-                    args.min_prop_size,
-                    prop_range,
-                ));
+            let dst = rng.gen_range(0..maxnid);
+
+            match args.format {
+                Format::Csv => {
+                    edge_line.push_str(&dst.to_string());
+                    if has_edge_props {
+                        edge_line.push_str("|");
+                        edge_line.push_str(&get_prop(
+                            &mut rng,
+                            &args.edge_prop_dist,
+                            args.min_prop_size,
+                            prop_range,
+                        ));
+                    }
+                    edge_line.push_str("\n");
+                    edgefile
+                        .write_all(edge_line.as_bytes())
+                        .expect("Failed to write edge");
+                    edge_line.truncate(prefix_len);
+                }
+                Format::Binary | Format::Indexed => {
+                    // Fixed 12-byte record: (u32 src, u32 dst, f32 weight). The weight reuses the
+                    // same edge-property distribution machinery as the CSV property blob, just
+                    // emitting the sampled float directly instead of urlencoding random bytes.
+                    let weight = sample_prop_dist(&mut rng, &args.edge_prop_dist) as f32;
+                    edgefile
+                        .write_all(&(nid as u32).to_le_bytes())
+                        .expect("Failed to write edge");
+                    edgefile
+                        .write_all(&(dst as u32).to_le_bytes())
+                        .expect("Failed to write edge");
+                    edgefile
+                        .write_all(&weight.to_le_bytes())
+                        .expect("Failed to write edge");
+                }
+                Format::Gfa => {
+                    // `L <src> + <dst> + 0M`, with edge properties folded into an optional tag.
+                    let mut line = format!("L\t{}\t+\t{}\t+\t0M", nid, dst);
+                    if has_edge_props {
+                        let prop = get_prop(
+                            &mut rng,
+                            &args.edge_prop_dist,
+                            args.min_prop_size,
+                            prop_range,
+                        );
+                        line.push_str("\tep:Z:");
+                        line.push_str(&prop);
+                    }
+                    line.push_str("\n");
+                    edgefile
+                        .write_all(line.as_bytes())
+                        .expect("Failed to write GFA link");
+                }
             }
-            edge_line.push_str("\n");
-            edgefile
-                .write_all(edge_line.as_bytes())
-                .expect("Failed to write edge");
-            edge_line.truncate(prefix_len);
         }
         edge_line.clear();
     }
 }
 
 fn combine_chunks(args: &Args) {
+    let edges_name = combined_edges_name(&args.format);
+
     let nodefile = OpenOptions::new()
         .read(true)
         .write(true)
@@ -244,8 +424,8 @@ fn combine_chunks(args: &Args) {
     let edgefile = OpenOptions::new()
         .read(true)
         .write(true)
-        .open("edges.csv")
-        .expect("Failed to create edges.csv");
+        .open(edges_name)
+        .expect("Failed to create edge file");
 
     // Determine how big each chunk was and compute a prefix sum of the lengths (note that the
     // output files already have headers, so we need to account for those in the offsets)
@@ -258,7 +438,7 @@ fn combine_chunks(args: &Args) {
         nodeoffsets.push(nodeoffsets[i] + childnodefile.metadata().unwrap().len());
         nodefiles.push(childnodefile);
 
-        let childedgefile = File::open(format!("edges_{}.csv", i)).unwrap();
+        let childedgefile = File::open(chunk_edges_name(i, &args.format)).unwrap();
         edgeoffsets.push(edgeoffsets[i] + childedgefile.metadata().unwrap().len());
         edgefiles.push(childedgefile);
     }
@@ -280,6 +460,9 @@ fn combine_chunks(args: &Args) {
         let estart = edgeoffsets[i];
 
         let total_bytes = nodeoffsets[i + 1] - nstart + edgeoffsets[i + 1] - estart;
+        let format = args.format.clone();
+        let n_nodes = args.n_nodes;
+        let nprocs = args.nprocs;
         children.push(thread::spawn(move || {
             let mut nodefile = OpenOptions::new()
                 .write(true)
@@ -291,48 +474,68 @@ fn combine_chunks(args: &Args) {
 
             let mut edgefile = OpenOptions::new()
                 .write(true)
-                .open("edges.csv")
-                .expect("Failed to create edges.csv");
+                .open(combined_edges_name(&format))
+                .expect("Failed to create edge file");
             edgefile
                 .seek(SeekFrom::Start(estart))
-                .expect("Failed to seek edges.csv");
+                .expect("Failed to seek edge file");
 
             let childnodes_name = format!("nodes_{}.csv", i);
-            let childedges_name = format!("edges_{}.csv", i);
-
-            let childnodefile =
-                BufReader::new(File::open(&childnodes_name).expect("Failed to open node file"));
-            let childedgefile =
-                BufReader::new(File::open(&childedges_name).expect("Failed to open edge file"));
-
-            let mut nodefile = BufWriter::new(nodefile);
-            let mut edgefile = BufWriter::new(edgefile);
+            let childedges_name = chunk_edges_name(i, &format);
 
             let mut pbar = pbar(Some(total_bytes as usize));
 
-            for line in childnodefile.lines().flatten() {
-                nodefile
-                    .write_all(line.as_bytes())
-                    .expect("Failed to write to nodefile");
-
-                nodefile
-                    .write_all(b"\n")
-                    .expect("Failed to write to nodefile");
-                pbar.update(line.as_bytes().len() + 1).expect("");
-            }
+            // Both the byte ranges in the combined files and the exact byte length of each child
+            // file are already known, and no content transformation is needed, so just transfer
+            // the raw bytes rather than reparsing and rewriting lines.
+            let mut childnodefile =
+                File::open(&childnodes_name).expect("Failed to open node file");
+            let copied = std::io::copy(&mut childnodefile, &mut nodefile)
+                .expect("Failed to copy node file");
+            pbar.update(copied as usize).expect("");
             std::fs::remove_file(childnodes_name).expect("failed to remove child node file");
 
-            for line in childedgefile.lines().flatten() {
-                edgefile
-                    .write_all(line.as_bytes())
-                    .expect("Failed to write to edgefile");
+            let mut childedgefile =
+                File::open(&childedges_name).expect("Failed to open edge file");
+            let copied = std::io::copy(&mut childedgefile, &mut edgefile)
+                .expect("Failed to copy edge file");
+            pbar.update(copied as usize).expect("");
+            std::fs::remove_file(childedges_name).expect("failed to remove child edge file");
 
-                edgefile
-                    .write_all(b"\n")
-                    .expect("Failed to write to edgefile");
-                pbar.update(line.as_bytes().len() + 1).expect("");
+            if format == Format::Indexed {
+                // Relocate this chunk's node offsets from "relative to its own edge file" to
+                // "relative to the start of the combined file" by adding estart (which already
+                // counts the header table), then backfill that
+                // contiguous slice of the header table - nodes in this chunk occupy a contiguous
+                // id range, so it's a single seek + write.
+                let chunksize = n_nodes / nprocs;
+                let node_start = chunksize * i;
+                let header_name = chunk_header_name(i);
+                let mut header_bytes = Vec::new();
+                File::open(&header_name)
+                    .expect("Failed to open header file")
+                    .read_to_end(&mut header_bytes)
+                    .expect("Failed to read header file");
+
+                let mut fixed = Vec::with_capacity(header_bytes.len());
+                for entry in header_bytes.chunks_exact(HEADER_ENTRY_SIZE as usize) {
+                    let local_offset = u64::from_le_bytes(entry[0..8].try_into().unwrap());
+                    fixed.extend_from_slice(&(local_offset + estart).to_le_bytes());
+                    fixed.extend_from_slice(&entry[8..12]);
+                }
+
+                let mut headertable = OpenOptions::new()
+                    .write(true)
+                    .open(combined_edges_name(&format))
+                    .expect("Failed to open edge file");
+                headertable
+                    .seek(SeekFrom::Start(node_start as u64 * HEADER_ENTRY_SIZE))
+                    .expect("Failed to seek header table");
+                headertable
+                    .write_all(&fixed)
+                    .expect("Failed to write header table");
+                std::fs::remove_file(header_name).expect("failed to remove child header file");
             }
-            std::fs::remove_file(childedges_name).expect("failed to remove child edge file");
         }));
     }
     for child in children {
@@ -341,6 +544,21 @@ fn combine_chunks(args: &Args) {
 }
 
 fn generate(args: Args) {
+    if args.edge_dist == Dist::PowerLaw {
+        assert!(
+            args.k_max < args.n_nodes,
+            "k_max must be less than n_nodes for the power-law edge distribution"
+        );
+    }
+    assert!(
+        args.node_prop_dist != Dist::PowerLaw,
+        "--node-prop-dist power-law is not supported; power-law is only valid for --edge-dist"
+    );
+    assert!(
+        args.edge_prop_dist != Dist::PowerLaw,
+        "--edge-prop-dist power-law is not supported; power-law is only valid for --edge-dist"
+    );
+
     create_dir(&args.outdir);
     assert!(env::set_current_dir(&args.outdir).is_ok());
 
@@ -349,10 +567,26 @@ fn generate(args: Args) {
         nodefile
             .write_all(b"NodeID|data\n")
             .expect("Failed to write node header");
-        let mut edgefile = File::create("edges.csv").expect("Failed to create edges.csv");
-        edgefile
-            .write_all(b"SrcID|DstID\n")
-            .expect("Failed to write edge header");
+        let mut edgefile = File::create(combined_edges_name(&args.format))
+            .expect("Failed to create edge file");
+        match args.format {
+            Format::Csv => edgefile
+                .write_all(b"SrcID|DstID\n")
+                .expect("Failed to write edge header"),
+            Format::Binary => edgefile
+                .write_all(&(args.n_nodes as u64).to_le_bytes())
+                .expect("Failed to write edge header"),
+            Format::Indexed => {
+                // Reserve the per-node offset table up front; combine_chunks backfills each
+                // entry once it knows where every chunk's adjacency data landed.
+                edgefile
+                    .set_len(args.n_nodes as u64 * HEADER_ENTRY_SIZE)
+                    .expect("Failed to reserve edge header table");
+            }
+            Format::Gfa => edgefile
+                .write_all(b"H\tVN:Z:1.0\n")
+                .expect("Failed to write GFA header"),
+        }
     }
 
     let mut children = vec![];